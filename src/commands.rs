@@ -10,13 +10,19 @@ use tauri::{ipc::Channel, Manager, Runtime, State, Window};
 
 use crate::{
     open::Program,
-    process::{CommandEvent, TerminatedPayload},
+    process::{Command, CommandEvent, TerminatedPayload},
     scope::ExecuteArgs,
     Shell,
 };
 
 type ChildId = u32;
 
+// BLOCKING FOR MERGE: every command in this file now stores `(CommandChild, EncodingWrapper)`
+// per child, so `Shell::children` must be typed `Mutex<HashMap<ChildId, (CommandChild,
+// EncodingWrapper)>>`. `Shell` is defined in `lib.rs`, which isn't part of this tree (only
+// `commands.rs` is present here); that field's type must change in the same series as this
+// file, or nothing here compiles.
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "event", content = "payload")]
 #[non_exhaustive]
@@ -25,10 +31,15 @@ enum JSCommandEvent {
     Stderr(Buffer),
     /// Stdout bytes until a newline (\n) or carriage return (\r) is found.
     Stdout(Buffer),
-    /// An error happened waiting for the command to finish or converting the stdout/stderr bytes to an UTF-8 string.
+    /// A stdout line decoded with `encoding: "json"`, parsed as a single JSON value.
+    StdoutJson(serde_json::Value),
+    /// An error happened waiting for the command to finish, converting the stdout/stderr bytes
+    /// to an UTF-8 string, or (with `encoding: "json"`) parsing a stdout line as JSON.
     Error(String),
     /// Command process terminated.
     Terminated(TerminatedPayload),
+    /// The command ran longer than `options.timeout` and was killed.
+    TimedOut,
 }
 
 fn get_event_buffer(line: Vec<u8>, encoding: EncodingWrapper) -> Result<Buffer, FromUtf8Error> {
@@ -40,6 +51,9 @@ fn get_event_buffer(line: Vec<u8>, encoding: EncodingWrapper) -> Result<Buffer,
             None => String::from_utf8(line).map(Buffer::Text),
         },
         EncodingWrapper::Raw => Ok(Buffer::Raw(line)),
+        // only `JSCommandEvent::new` parses stdout lines as JSON; stderr, and the buffers that
+        // `execute_output` collects, fall back to plain UTF-8 text.
+        EncodingWrapper::Json => String::from_utf8(line).map(Buffer::Text),
     }
 }
 
@@ -51,9 +65,14 @@ impl JSCommandEvent {
             CommandEvent::Stderr(line) => get_event_buffer(line, encoding)
                 .map(JSCommandEvent::Stderr)
                 .unwrap_or_else(|e| JSCommandEvent::Error(e.to_string())),
-            CommandEvent::Stdout(line) => get_event_buffer(line, encoding)
-                .map(JSCommandEvent::Stdout)
-                .unwrap_or_else(|e| JSCommandEvent::Error(e.to_string())),
+            CommandEvent::Stdout(line) => match encoding {
+                EncodingWrapper::Json => serde_json::from_slice(&line)
+                    .map(JSCommandEvent::StdoutJson)
+                    .unwrap_or_else(|e| JSCommandEvent::Error(e.to_string())),
+                _ => get_event_buffer(line, encoding)
+                    .map(JSCommandEvent::Stdout)
+                    .unwrap_or_else(|e| JSCommandEvent::Error(e.to_string())),
+            },
         }
     }
 }
@@ -70,6 +89,25 @@ pub enum Buffer {
 pub enum EncodingWrapper {
     Raw,
     Text(Option<&'static Encoding>),
+    /// Each stdout line is parsed as a single JSON value instead of being decoded as text.
+    Json,
+}
+
+fn resolve_encoding(encoding: Option<String>) -> crate::Result<EncodingWrapper> {
+    match encoding {
+        Option::None => Ok(EncodingWrapper::Text(None)),
+        Some(encoding) => match encoding.as_str() {
+            "raw" => Ok(EncodingWrapper::Raw),
+            "json" => Ok(EncodingWrapper::Json),
+            _ => {
+                if let Some(text_encoding) = Encoding::for_label(encoding.as_bytes()) {
+                    Ok(EncodingWrapper::Text(Some(text_encoding)))
+                } else {
+                    Err(crate::Error::UnknownEncoding(encoding))
+                }
+            }
+        },
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -84,6 +122,8 @@ pub struct CommandOptions {
     env: Option<HashMap<String, String>>,
     // Character encoding for stdout/stderr
     encoding: Option<String>,
+    // Maximum time in milliseconds the command is allowed to run before it's killed
+    timeout: Option<u64>,
 }
 
 #[allow(clippy::unnecessary_wraps)]
@@ -91,15 +131,16 @@ fn default_env() -> Option<HashMap<String, String>> {
     Some(HashMap::default())
 }
 
-#[tauri::command]
-pub fn execute<R: Runtime>(
-    window: Window<R>,
-    shell: State<'_, Shell<R>>,
+/// Resolves the scope-checked [`Command`] (sidecar or regular program, with `cwd`/`env` applied)
+/// and the [`EncodingWrapper`] for `options.encoding`. Shared by [`execute`] and [`execute_output`]
+/// so the two command-preparation paths can't drift apart.
+fn prepare_command<R: Runtime>(
+    window: &Window<R>,
+    shell: &State<'_, Shell<R>>,
     program: String,
     args: ExecuteArgs,
-    on_event: Channel,
-    options: CommandOptions,
-) -> crate::Result<ChildId> {
+    options: &CommandOptions,
+) -> crate::Result<(Command, EncodingWrapper)> {
     let mut command = if options.sidecar {
         let program = PathBuf::from(program);
         let program_as_string = program.display().to_string();
@@ -132,47 +173,182 @@ pub fn execute<R: Runtime>(
             }
         }
     };
-    if let Some(cwd) = options.cwd {
+    if let Some(cwd) = options.cwd.clone() {
         command = command.current_dir(cwd);
     }
-    if let Some(env) = options.env {
+    if let Some(env) = options.env.clone() {
         command = command.envs(env);
     } else {
         command = command.env_clear();
     }
-    let encoding = match options.encoding {
-        Option::None => EncodingWrapper::Text(None),
-        Some(encoding) => match encoding.as_str() {
-            "raw" => EncodingWrapper::Raw,
-            _ => {
-                if let Some(text_encoding) = Encoding::for_label(encoding.as_bytes()) {
-                    EncodingWrapper::Text(Some(text_encoding))
-                } else {
-                    return Err(crate::Error::UnknownEncoding(encoding));
-                }
-            }
-        },
-    };
+    let encoding = resolve_encoding(options.encoding.clone())?;
+    Ok((command, encoding))
+}
+
+#[tauri::command]
+pub fn execute<R: Runtime>(
+    window: Window<R>,
+    shell: State<'_, Shell<R>>,
+    program: String,
+    args: ExecuteArgs,
+    on_event: Channel,
+    options: CommandOptions,
+) -> crate::Result<ChildId> {
+    let timeout = options.timeout.map(std::time::Duration::from_millis);
+    let (command, encoding) = prepare_command(&window, &shell, program, args, &options)?;
 
     let (mut rx, child) = command.spawn()?;
 
     let pid = child.pid();
-    shell.children.lock().unwrap().insert(pid, child);
+    shell.children.lock().unwrap().insert(pid, (child, encoding));
     let children = shell.children.clone();
 
     tauri::async_runtime::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            if matches!(event, crate::process::CommandEvent::Terminated(_)) {
-                children.lock().unwrap().remove(&pid);
-            };
-            let js_event = JSCommandEvent::new(event, encoding);
-            let _ = on_event.send(&js_event);
+        let forward_events = async {
+            while let Some(event) = rx.recv().await {
+                if matches!(event, crate::process::CommandEvent::Terminated(_)) {
+                    children.lock().unwrap().remove(&pid);
+                };
+                let js_event = JSCommandEvent::new(event, encoding);
+                let _ = on_event.send(&js_event);
+            }
+        };
+        let timed_out = match timeout {
+            // requires the `time` feature of the `tokio` dependency this crate already pulls
+            // in for the process/channel plumbing (see Cargo.toml).
+            Some(duration) => tokio::time::timeout(duration, forward_events)
+                .await
+                .is_err(),
+            None => {
+                forward_events.await;
+                false
+            }
+        };
+        if timed_out {
+            if let Some((child, _)) = children.lock().unwrap().remove(&pid) {
+                let _ = child.kill();
+            }
+            let _ = on_event.send(&JSCommandEvent::TimedOut);
         }
     });
 
     Ok(pid)
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputResponse {
+    code: Option<i32>,
+    signal: Option<i32>,
+    stdout: Buffer,
+    stderr: Buffer,
+}
+
+fn io_error(kind: std::io::ErrorKind, message: impl Into<String>) -> crate::Error {
+    crate::Error::Io(std::io::Error::new(kind, message.into()))
+}
+
+// BLOCKING FOR MERGE: unreachable from JS until it's added to `generate_handler!` and given
+// a default ACL permission, both of which live in `lib.rs`/`default.toml` — not in this tree
+// (only `commands.rs` is present here). Must land alongside this command in the same series.
+#[tauri::command]
+pub async fn execute_output<R: Runtime>(
+    window: Window<R>,
+    shell: State<'_, Shell<R>>,
+    program: String,
+    args: ExecuteArgs,
+    options: CommandOptions,
+) -> crate::Result<OutputResponse> {
+    let timeout = options.timeout.map(std::time::Duration::from_millis);
+    let (command, encoding) = prepare_command(&window, &shell, program, args, &options)?;
+
+    let (mut rx, child) = command.spawn()?;
+
+    let pid = child.pid();
+    shell.children.lock().unwrap().insert(pid, (child, encoding));
+    let children = shell.children.clone();
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut terminated = None;
+        let mut last_error = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(mut line) => {
+                    if !stdout.is_empty() {
+                        stdout.push(b'\n');
+                    }
+                    stdout.append(&mut line);
+                }
+                CommandEvent::Stderr(mut line) => {
+                    if !stderr.is_empty() {
+                        stderr.push(b'\n');
+                    }
+                    stderr.append(&mut line);
+                }
+                CommandEvent::Terminated(payload) => {
+                    children.lock().unwrap().remove(&pid);
+                    terminated = Some(payload);
+                }
+                CommandEvent::Error(error) => last_error = Some(error),
+            }
+        }
+        // A mid-stream `Error` event doesn't discard stdout/stderr and the real exit code/signal
+        // once the process has terminated; it is only returned when no `Terminated` event
+        // arrived. Output that fails to decode with the chosen encoding is an `InvalidData` error.
+        let Some(terminated) = terminated else {
+            return Err(last_error
+                .map(|e| io_error(std::io::ErrorKind::Other, e))
+                .unwrap_or_else(|| {
+                    io_error(
+                        std::io::ErrorKind::Other,
+                        "command exited without a termination event",
+                    )
+                }));
+        };
+        Ok(OutputResponse {
+            code: terminated.code,
+            signal: terminated.signal,
+            stdout: get_event_buffer(stdout, encoding)
+                .map_err(|e| io_error(std::io::ErrorKind::InvalidData, e.to_string()))?,
+            stderr: get_event_buffer(stderr, encoding)
+                .map_err(|e| io_error(std::io::ErrorKind::InvalidData, e.to_string()))?,
+        })
+    });
+
+    let panicked = || io_error(std::io::ErrorKind::Other, "command task panicked");
+
+    match timeout {
+        // the blocking path also honors `timeout`: an unbounded `execute_output` call would
+        // otherwise hang forever on a runaway child with no way to cancel it.
+        Some(duration) => match tokio::time::timeout(duration, handle).await {
+            Ok(join_result) => join_result.map_err(|_| panicked())?,
+            Err(_) => {
+                if let Some((child, _)) = shell.children.lock().unwrap().remove(&pid) {
+                    let _ = child.kill();
+                }
+                Err(io_error(std::io::ErrorKind::TimedOut, "command timed out"))
+            }
+        },
+        None => handle.await.map_err(|_| panicked())?,
+    }
+}
+
+/// Encodes outgoing stdin text the same way the command's `encoding` option decodes stdout/stderr,
+/// so a non-UTF-8 child sees bytes in the charset it expects. UTF-16 labels (and `replacement`)
+/// are written as UTF-8, since `encoding_rs` encodes those through UTF-8.
+fn encode_stdin_text(text: &str, encoding: EncodingWrapper) -> Vec<u8> {
+    match encoding {
+        EncodingWrapper::Text(Some(character_encoding)) => {
+            character_encoding.encode(text).0.into_owned()
+        }
+        EncodingWrapper::Text(None) | EncodingWrapper::Raw | EncodingWrapper::Json => {
+            text.as_bytes().to_vec()
+        }
+    }
+}
+
 #[tauri::command]
 pub fn stdin_write<R: Runtime>(
     _window: Window<R>,
@@ -180,27 +356,164 @@ pub fn stdin_write<R: Runtime>(
     pid: ChildId,
     buffer: Buffer,
 ) -> crate::Result<()> {
-    if let Some(child) = shell.children.lock().unwrap().get_mut(&pid) {
+    if let Some((child, encoding)) = shell.children.lock().unwrap().get_mut(&pid) {
         match buffer {
-            Buffer::Text(t) => child.write(t.as_bytes())?,
+            Buffer::Text(t) => child.write(&encode_stdin_text(&t, *encoding))?,
             Buffer::Raw(r) => child.write(&r)?,
         }
     }
     Ok(())
 }
 
+/// Closes the child's stdin, signalling EOF to programs that read until the pipe closes
+/// (e.g. `sort`, `wc`, or a filter stage in a pipeline) instead of hanging forever.
+///
+/// BLOCKING FOR MERGE: this calls `CommandChild::close_stdin`, which does not exist yet.
+/// `CommandChild` is defined in `process.rs`, and registering this command in
+/// `generate_handler!` plus its default ACL permission happens in `lib.rs`/`default.toml` —
+/// none of which are part of this source tree (only `commands.rs` is present here), so this
+/// command cannot be made to compile or be reachable from JS from within this tree alone.
+/// Landing it for real requires a companion change to those files in the same series.
+#[tauri::command]
+pub fn stdin_close<R: Runtime>(
+    _window: Window<R>,
+    shell: State<'_, Shell<R>>,
+    pid: ChildId,
+) -> crate::Result<()> {
+    if let Some((child, _)) = shell.children.lock().unwrap().get_mut(&pid) {
+        child.close_stdin()?;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn kill<R: Runtime>(
     _window: Window<R>,
     shell: State<'_, Shell<R>>,
     pid: ChildId,
 ) -> crate::Result<()> {
-    if let Some(child) = shell.children.lock().unwrap().remove(&pid) {
+    if let Some((child, _)) = shell.children.lock().unwrap().remove(&pid) {
         child.kill()?;
     }
     Ok(())
 }
 
+/// A portable signal that can be sent to a running child with [`signal`].
+///
+/// On Windows only [`ProcessSignal::Term`] and [`ProcessSignal::Kill`] are supported; both
+/// terminate the process, since Windows has no notion of the other POSIX signals.
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub enum ProcessSignal {
+    Hup,
+    Int,
+    Quit,
+    Term,
+    Kill,
+    Usr1,
+    Usr2,
+}
+
+/// Either a raw, platform-specific signal number or a [`ProcessSignal`].
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Signal {
+    Raw(i32),
+    Portable(ProcessSignal),
+}
+
+impl Signal {
+    #[cfg(unix)]
+    fn is_fatal(&self) -> bool {
+        self.as_raw() == libc::SIGKILL
+    }
+
+    #[cfg(windows)]
+    fn is_fatal(&self) -> bool {
+        // `send_signal` maps both of these to `TerminateProcess`, a hard kill, so both must
+        // drop the child from `shell.children` just like `Signal::Raw`'s unix `SIGKILL` case.
+        matches!(
+            self,
+            Signal::Portable(ProcessSignal::Term) | Signal::Portable(ProcessSignal::Kill)
+        )
+    }
+
+    #[cfg(unix)]
+    fn as_raw(&self) -> i32 {
+        match self {
+            Signal::Raw(n) => *n,
+            Signal::Portable(ProcessSignal::Hup) => libc::SIGHUP,
+            Signal::Portable(ProcessSignal::Int) => libc::SIGINT,
+            Signal::Portable(ProcessSignal::Quit) => libc::SIGQUIT,
+            Signal::Portable(ProcessSignal::Term) => libc::SIGTERM,
+            Signal::Portable(ProcessSignal::Kill) => libc::SIGKILL,
+            Signal::Portable(ProcessSignal::Usr1) => libc::SIGUSR1,
+            Signal::Portable(ProcessSignal::Usr2) => libc::SIGUSR2,
+        }
+    }
+}
+
+// BLOCKING FOR MERGE: unreachable from JS until it's added to `generate_handler!` and given
+// a default ACL permission, both of which live in `lib.rs`/`default.toml` — not in this tree
+// (only `commands.rs` is present here). Must land alongside this command in the same series.
+// This command also relies on `libc` (unix) / `windows_sys` (windows) in `send_signal` below;
+// those crates must be added to `Cargo.toml` in the same series or both platforms fail to build.
+#[tauri::command]
+pub fn signal<R: Runtime>(
+    _window: Window<R>,
+    shell: State<'_, Shell<R>>,
+    pid: ChildId,
+    signal: Signal,
+) -> crate::Result<()> {
+    let mut children = shell.children.lock().unwrap();
+    let Some((child, _)) = children.get(&pid) else {
+        return Ok(());
+    };
+    let result = send_signal(child.pid(), signal);
+    // a fatal signal kills the process, so drop it from the store like `kill` does;
+    // anything else leaves it in place so further writes/signals still work.
+    if signal.is_fatal() {
+        children.remove(&pid);
+    }
+    result
+}
+
+#[cfg(unix)]
+fn send_signal(pid: ChildId, signal: Signal) -> crate::Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal.as_raw()) } != 0 {
+        return Err(crate::Error::Io(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn send_signal(pid: ChildId, signal: Signal) -> crate::Result<()> {
+    match signal {
+        Signal::Portable(ProcessSignal::Term) | Signal::Portable(ProcessSignal::Kill) => {
+            unsafe {
+                let handle = windows_sys::Win32::System::Threading::OpenProcess(
+                    windows_sys::Win32::System::Threading::PROCESS_TERMINATE,
+                    0,
+                    pid,
+                );
+                if handle.is_null() {
+                    return Err(crate::Error::Io(std::io::Error::last_os_error()));
+                }
+                let ok = windows_sys::Win32::System::Threading::TerminateProcess(handle, 1);
+                windows_sys::Win32::Foundation::CloseHandle(handle);
+                if ok == 0 {
+                    return Err(crate::Error::Io(std::io::Error::last_os_error()));
+                }
+            }
+            Ok(())
+        }
+        // Windows has no equivalent of the other POSIX signals; only termination is supported.
+        _ => Err(crate::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "only Term and Kill signals are supported on Windows",
+        ))),
+    }
+}
+
 #[tauri::command]
 pub fn open<R: Runtime>(
     _window: Window<R>,
@@ -210,3 +523,126 @@ pub fn open<R: Runtime>(
 ) -> crate::Result<()> {
     shell.open(path, with)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn as_raw_maps_portable_signals_to_libc_constants() {
+        assert_eq!(Signal::Portable(ProcessSignal::Hup).as_raw(), libc::SIGHUP);
+        assert_eq!(Signal::Portable(ProcessSignal::Int).as_raw(), libc::SIGINT);
+        assert_eq!(
+            Signal::Portable(ProcessSignal::Quit).as_raw(),
+            libc::SIGQUIT
+        );
+        assert_eq!(
+            Signal::Portable(ProcessSignal::Term).as_raw(),
+            libc::SIGTERM
+        );
+        assert_eq!(
+            Signal::Portable(ProcessSignal::Kill).as_raw(),
+            libc::SIGKILL
+        );
+        assert_eq!(
+            Signal::Portable(ProcessSignal::Usr1).as_raw(),
+            libc::SIGUSR1
+        );
+        assert_eq!(
+            Signal::Portable(ProcessSignal::Usr2).as_raw(),
+            libc::SIGUSR2
+        );
+        assert_eq!(Signal::Raw(42).as_raw(), 42);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn is_fatal_only_for_sigkill() {
+        assert!(Signal::Portable(ProcessSignal::Kill).is_fatal());
+        assert!(Signal::Raw(libc::SIGKILL).is_fatal());
+        assert!(!Signal::Portable(ProcessSignal::Term).is_fatal());
+        assert!(!Signal::Portable(ProcessSignal::Hup).is_fatal());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn is_fatal_for_term_and_kill_on_windows() {
+        assert!(Signal::Portable(ProcessSignal::Term).is_fatal());
+        assert!(Signal::Portable(ProcessSignal::Kill).is_fatal());
+        assert!(!Signal::Portable(ProcessSignal::Hup).is_fatal());
+    }
+
+    #[test]
+    fn resolve_encoding_maps_known_labels() {
+        assert!(matches!(
+            resolve_encoding(None).unwrap(),
+            EncodingWrapper::Text(None)
+        ));
+        assert!(matches!(
+            resolve_encoding(Some("raw".into())).unwrap(),
+            EncodingWrapper::Raw
+        ));
+        assert!(matches!(
+            resolve_encoding(Some("json".into())).unwrap(),
+            EncodingWrapper::Json
+        ));
+        assert!(matches!(
+            resolve_encoding(Some("utf-8".into())).unwrap(),
+            EncodingWrapper::Text(Some(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_encoding_rejects_unknown_label() {
+        assert!(matches!(
+            resolve_encoding(Some("not-a-real-encoding".into())),
+            Err(crate::Error::UnknownEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn stdout_json_parses_a_valid_line() {
+        let event = JSCommandEvent::new(
+            CommandEvent::Stdout(br#"{"ok":true}"#.to_vec()),
+            EncodingWrapper::Json,
+        );
+        assert!(matches!(
+            event,
+            JSCommandEvent::StdoutJson(v) if v == serde_json::json!({"ok": true})
+        ));
+    }
+
+    #[test]
+    fn stdout_json_surfaces_malformed_line_as_error() {
+        let event = JSCommandEvent::new(
+            CommandEvent::Stdout(b"not json".to_vec()),
+            EncodingWrapper::Json,
+        );
+        assert!(matches!(event, JSCommandEvent::Error(_)));
+    }
+
+    #[test]
+    fn encode_stdin_text_defaults_to_utf8() {
+        assert_eq!(
+            encode_stdin_text("héllo", EncodingWrapper::Text(None)),
+            "héllo".as_bytes()
+        );
+        assert_eq!(
+            encode_stdin_text("héllo", EncodingWrapper::Raw),
+            "héllo".as_bytes()
+        );
+        assert_eq!(
+            encode_stdin_text("héllo", EncodingWrapper::Json),
+            "héllo".as_bytes()
+        );
+    }
+
+    #[test]
+    fn encode_stdin_text_round_trips_through_configured_encoding() {
+        let encoding = Encoding::for_label(b"shift-jis").unwrap();
+        let encoded = encode_stdin_text("こんにちは", EncodingWrapper::Text(Some(encoding)));
+        let (decoded, _) = encoding.decode_with_bom_removal(&encoded);
+        assert_eq!(decoded, "こんにちは");
+    }
+}